@@ -2,13 +2,16 @@
 // CZI (Carl Zeiss Image) format decoder will be implemented here
 
 use crate::metadata::ImageMetadata;
+use crate::pixel_data::PixelData;
+use crate::registry::FormatDecoder;
+use crate::utils::DecodeOptions;
 use pyo3::prelude::*;
 
 pub fn decode_czi(_file_path: &str) -> PyResult<(Vec<Vec<Vec<u16>>>, ImageMetadata)> {
     // TODO: Implement CZI decoder
     // This will require parsing the CZI file format specification
     // and extracting both image data and rich metadata
-    
+
     Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
         "CZI decoder not yet implemented"
     ))
@@ -16,8 +19,35 @@ pub fn decode_czi(_file_path: &str) -> PyResult<(Vec<Vec<Vec<u16>>>, ImageMetada
 
 pub fn get_czi_metadata(_file_path: &str) -> PyResult<ImageMetadata> {
     // TODO: Implement CZI metadata extraction
-    
+
     Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
         "CZI metadata extraction not yet implemented"
     ))
+}
+
+/// `FormatDecoder` adapter for CZI files. Neither side is implemented yet,
+/// so `ZStackDecoder.capabilities()` can report that honestly.
+pub struct CziFormat;
+
+impl FormatDecoder for CziFormat {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["czi"]
+    }
+
+    fn supports_decode(&self) -> bool {
+        false
+    }
+
+    fn supports_metadata(&self) -> bool {
+        false
+    }
+
+    fn decode(&self, file_path: &str, _options: &DecodeOptions) -> PyResult<(PixelData, ImageMetadata)> {
+        let (data, metadata) = decode_czi(file_path)?;
+        Ok((PixelData::U16(data), metadata))
+    }
+
+    fn metadata(&self, file_path: &str) -> PyResult<ImageMetadata> {
+        get_czi_metadata(file_path)
+    }
 }
\ No newline at end of file
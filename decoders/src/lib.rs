@@ -2,11 +2,20 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyModule};
 
 pub mod tiff_decoder;
+pub mod tiff_encoder;
 pub mod czi_decoder;
+pub mod lsm_decoder;
+pub mod nd2_decoder;
 pub mod metadata;
+pub mod pixel_data;
+pub mod registry;
 pub mod utils;
 
 use crate::metadata::ImageMetadata;
+use crate::pixel_data::{PixelData, SampleFormat};
+use crate::registry::{find, registry};
+use crate::tiff_encoder::TiffCompression;
+use crate::utils::DecodeOptions;
 
 #[pyclass]
 pub struct ZStackDecoder {
@@ -14,6 +23,16 @@ pub struct ZStackDecoder {
     pub supported_formats: Vec<String>,
 }
 
+impl ZStackDecoder {
+    fn extension_of(file_path: &str) -> String {
+        std::path::Path::new(file_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+    }
+}
+
 #[pymethods]
 impl ZStackDecoder {
     #[new]
@@ -29,53 +48,96 @@ impl ZStackDecoder {
         }
     }
 
-    pub fn decode_file(&self, py: Python, file_path: &str) -> PyResult<PyObject> {
-        let extension = std::path::Path::new(file_path)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-
-        match extension.as_str() {
-            "tiff" | "tif" => {
-                let metadata = tiff_decoder::get_tiff_metadata(file_path)?;
-                let result = PyDict::new_bound(py);
-                result.set_item("metadata", metadata.to_dict(py)?)?;
-                Ok(result.into())
-            }
-            "czi" => {
-                // Placeholder for CZI decoder
-                Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
-                    "CZI format not yet implemented"
-                ))
-            }
-            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                format!("Unsupported file format: {}", extension)
-            )),
+    #[pyo3(signature = (file_path, options=None))]
+    pub fn decode_file(&self, py: Python, file_path: &str, options: Option<DecodeOptions>) -> PyResult<PyObject> {
+        let options = options.unwrap_or_default();
+        let extension = Self::extension_of(file_path);
+
+        let decoders = registry();
+        let decoder = find(&decoders, &extension).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unsupported file format: {}", extension))
+        })?;
+
+        if !decoder.supports_decode() {
+            return Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
+                format!("{} decoding is not yet implemented", extension)
+            ));
         }
+
+        let (data, metadata) = decoder.decode(file_path, &options)?;
+        let result = PyDict::new_bound(py);
+        result.set_item("data", data.into_py(py))?;
+        result.set_item("metadata", metadata.to_dict(py)?)?;
+        Ok(result.into())
     }
 
-    pub fn get_metadata(&self, file_path: &str) -> PyResult<PyObject> {
-        let extension = std::path::Path::new(file_path)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+    /// `data` is extracted according to `metadata["sample_format"]` so
+    /// integer, signed-integer, and float Z-stacks (as returned by
+    /// `decode_file`) all round-trip back to disk, not just `u16` ones.
+    pub fn encode_tiff(
+        &self,
+        data: &Bound<PyAny>,
+        metadata: &Bound<PyDict>,
+        path: &str,
+        compression: TiffCompression,
+    ) -> PyResult<()> {
+        let metadata = ImageMetadata::from_dict(metadata)?;
+        let pixel_data = match metadata.sample_format {
+            SampleFormat::Unsigned => PixelData::U16(data.extract()?),
+            SampleFormat::Signed => PixelData::I32(data.extract()?),
+            SampleFormat::Float => PixelData::F32(data.extract()?),
+        };
+        tiff_encoder::_encode_tiff(&pixel_data, &metadata, path, compression)
+    }
+
+    pub fn get_metadata(&self, py: Python, file_path: &str) -> PyResult<PyObject> {
+        let extension = Self::extension_of(file_path);
+
+        let decoders = registry();
+        let decoder = find(&decoders, &extension).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unsupported file format: {}", extension))
+        })?;
 
-        match extension.as_str() {
-            "tiff" | "tif" => {
-                let metadata = tiff_decoder::get_tiff_metadata(file_path)?;
-                Python::with_gil(|py| metadata.to_dict(py))
+        if !decoder.supports_metadata() {
+            return Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
+                format!("{} metadata extraction is not yet implemented", extension)
+            ));
+        }
+
+        let metadata = decoder.metadata(file_path)?;
+        metadata.to_dict(py)
+    }
+
+    /// Report, per advertised extension, whether decoding and/or metadata
+    /// extraction are actually available yet, so `supported_formats` stops
+    /// being a misleading wishlist.
+    pub fn capabilities(&self, py: Python) -> PyResult<PyObject> {
+        let decoders = registry();
+        let result = PyDict::new_bound(py);
+
+        for ext in &self.supported_formats {
+            let caps = PyDict::new_bound(py);
+            match find(&decoders, ext) {
+                Some(decoder) => {
+                    caps.set_item("decode", decoder.supports_decode())?;
+                    caps.set_item("metadata", decoder.supports_metadata())?;
+                }
+                None => {
+                    caps.set_item("decode", false)?;
+                    caps.set_item("metadata", false)?;
+                }
             }
-            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                format!("Unsupported file format: {}", extension)
-            )),
+            result.set_item(ext, caps)?;
         }
+
+        Ok(result.into())
     }
 }
 
 #[pymodule]
 fn zstack_decoders(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<ZStackDecoder>()?;
+    m.add_class::<DecodeOptions>()?;
+    m.add_class::<TiffCompression>()?;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,147 @@
+use crate::metadata::ImageMetadata;
+use crate::pixel_data::PixelData;
+use crate::registry::FormatDecoder;
+use crate::tiff_decoder;
+use crate::utils::DecodeOptions;
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+use tiff::decoder::ifd::Value;
+use tiff::decoder::Decoder;
+use tiff::tags::Tag;
+
+/// Zeiss's private TIFF tag carrying the `CZ_LSM_INFO` struct.
+const CZ_LSM_INFO_TAG: u16 = 34412;
+
+// Byte offsets of the fields we care about within the (little-endian)
+// CZ_LSM_INFO struct.
+const MAGIC_NUMBER_OFFSET: usize = 0;
+const STRUCTURE_SIZE_OFFSET: usize = 4;
+const DIMENSION_Z_OFFSET: usize = 16;
+const DIMENSION_CHANNELS_OFFSET: usize = 20;
+const VOXEL_SIZE_X_OFFSET: usize = 40;
+const VOXEL_SIZE_Y_OFFSET: usize = 48;
+const VOXEL_SIZE_Z_OFFSET: usize = 56;
+
+// CZ_LSM_INFO always starts with this magic number; a private tag 34412
+// that doesn't start with it isn't actually a CZ_LSM_INFO struct.
+const CZ_LSM_MAGIC_NUMBER: u32 = 0x04d6_c341;
+// Smallest structure_size that still covers VoxelSizeZ, the last field we read.
+const MIN_STRUCTURE_SIZE: u32 = (VOXEL_SIZE_Z_OFFSET + 8) as u32;
+
+fn read_u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes.get(offset..offset + 4).map(|s| u32::from_le_bytes(s.try_into().unwrap()))
+}
+
+fn read_f64_at(bytes: &[u8], offset: usize) -> Option<f64> {
+    bytes.get(offset..offset + 8).map(|s| f64::from_le_bytes(s.try_into().unwrap()))
+}
+
+/// LSM files are TIFF containers, so read the rest of the metadata via the
+/// regular TIFF path and only layer the `CZ_LSM_INFO` fields on top: voxel
+/// sizes (meters, converted to um/pixel) and the true Z/channel dimensions.
+///
+/// `expected_planes`, when known (i.e. pixel data was actually decoded),
+/// must equal `dimension_z * dimension_channels` — real LSM stacks commonly
+/// store one IFD per (Z, channel) combination. `metadata.depth` is then set
+/// to `DimensionZ` and `metadata.channels` to `DimensionChannels`, so with
+/// more than one channel `len(data)` is `depth * channels`, not `depth`.
+fn populate_lsm_metadata(file_path: &str, metadata: &mut ImageMetadata, expected_planes: Option<usize>) -> PyResult<()> {
+    let file = File::open(file_path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open file: {}", e)))?;
+    let mut decoder = Decoder::new(BufReader::new(file))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to create TIFF decoder: {}", e)))?;
+
+    let raw = match decoder.find_tag(Tag::Unknown(CZ_LSM_INFO_TAG)) {
+        Ok(Some(value)) => value,
+        // Not an LSM-tagged file (or the tag couldn't be read): leave the
+        // plain TIFF metadata as-is rather than failing the whole decode.
+        _ => return Ok(()),
+    };
+
+    let bytes: Vec<u8> = match raw {
+        Value::List(values) => values
+            .into_iter()
+            .filter_map(|v| match v {
+                Value::Byte(b) => Some(b),
+                _ => None,
+            })
+            .collect(),
+        _ => return Ok(()),
+    };
+
+    // Tag 34412 is only reserved for CZ_LSM_INFO, not guaranteed to carry
+    // it — validate the magic number and declared size before trusting any
+    // of the byte offsets below, the same way an absent tag is a no-op.
+    if read_u32_at(&bytes, MAGIC_NUMBER_OFFSET) != Some(CZ_LSM_MAGIC_NUMBER) {
+        return Ok(());
+    }
+    if read_u32_at(&bytes, STRUCTURE_SIZE_OFFSET).unwrap_or(0) < MIN_STRUCTURE_SIZE {
+        return Ok(());
+    }
+
+    let dimension_z = read_u32_at(&bytes, DIMENSION_Z_OFFSET);
+    let dimension_channels = read_u32_at(&bytes, DIMENSION_CHANNELS_OFFSET);
+
+    if let Some(planes) = expected_planes {
+        if let Some(z) = dimension_z {
+            let channels = dimension_channels.unwrap_or(1).max(1);
+            let expected = z as usize * channels as usize;
+            if expected != planes {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "CZ_LSM_INFO declares {} Z-slices x {} channels = {} planes, but the TIFF container has {}",
+                    z, channels, expected, planes
+                )));
+            }
+        }
+    }
+
+    if let Some(depth) = dimension_z {
+        metadata.depth = depth;
+    }
+    if let Some(channels) = dimension_channels {
+        metadata.channels = channels;
+    }
+    if let Some(voxel_x) = read_f64_at(&bytes, VOXEL_SIZE_X_OFFSET) {
+        metadata.pixel_size_x = Some(voxel_x * 1_000_000.0);
+    }
+    if let Some(voxel_y) = read_f64_at(&bytes, VOXEL_SIZE_Y_OFFSET) {
+        metadata.pixel_size_y = Some(voxel_y * 1_000_000.0);
+    }
+    if let Some(voxel_z) = read_f64_at(&bytes, VOXEL_SIZE_Z_OFFSET) {
+        metadata.pixel_size_z = Some(voxel_z * 1_000_000.0);
+    }
+
+    Ok(())
+}
+
+/// `FormatDecoder` adapter for Zeiss LSM files.
+pub struct LsmFormat;
+
+impl FormatDecoder for LsmFormat {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["lsm"]
+    }
+
+    fn supports_decode(&self) -> bool {
+        true
+    }
+
+    fn supports_metadata(&self) -> bool {
+        true
+    }
+
+    fn decode(&self, file_path: &str, options: &DecodeOptions) -> PyResult<(PixelData, ImageMetadata)> {
+        let (data, mut metadata) = tiff_decoder::_decode_tiff(file_path, options)?;
+        populate_lsm_metadata(file_path, &mut metadata, Some(data.depth()))?;
+        Ok((data, metadata))
+    }
+
+    fn metadata(&self, file_path: &str) -> PyResult<ImageMetadata> {
+        let mut metadata = tiff_decoder::get_tiff_metadata(file_path)?;
+        // No pixel data is decoded on this path, so there's no plane count
+        // to validate CZ_LSM_INFO's declared dimensions against.
+        populate_lsm_metadata(file_path, &mut metadata, None)?;
+        Ok(metadata)
+    }
+}
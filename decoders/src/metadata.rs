@@ -1,5 +1,6 @@
+use crate::pixel_data::{PlaneStats, SampleFormat};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyList};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -10,6 +11,7 @@ pub struct ImageMetadata {
     pub depth: u32,
     pub channels: u32,
     pub bit_depth: u16,
+    pub sample_format: SampleFormat,
     pub pixel_size_x: Option<f64>,
     pub pixel_size_y: Option<f64>,
     pub pixel_size_z: Option<f64>,
@@ -19,6 +21,7 @@ pub struct ImageMetadata {
     pub channel_names: Vec<String>,
     pub exposure_times: Vec<f64>,
     pub custom_metadata: HashMap<String, String>,
+    pub plane_stats: Vec<PlaneStats>,
 }
 
 impl ImageMetadata {
@@ -29,6 +32,7 @@ impl ImageMetadata {
             depth: 0,
             channels: 0,
             bit_depth: 0,
+            sample_format: SampleFormat::Unsigned,
             pixel_size_x: None,
             pixel_size_y: None,
             pixel_size_z: None,
@@ -38,6 +42,7 @@ impl ImageMetadata {
             channel_names: Vec::new(),
             exposure_times: Vec::new(),
             custom_metadata: HashMap::new(),
+            plane_stats: Vec::new(),
         }
     }
 
@@ -49,7 +54,8 @@ impl ImageMetadata {
         dict.set_item("depth", self.depth)?;
         dict.set_item("channels", self.channels)?;
         dict.set_item("bit_depth", self.bit_depth)?;
-        
+        dict.set_item("sample_format", self.sample_format.as_str())?;
+
         if let Some(px) = self.pixel_size_x {
             dict.set_item("pixel_size_x", px)?;
         }
@@ -78,7 +84,83 @@ impl ImageMetadata {
             custom_dict.set_item(key, value)?;
         }
         dict.set_item("custom_metadata", custom_dict)?;
-        
+
+        let stats_list = PyList::empty_bound(py);
+        for stats in &self.plane_stats {
+            stats_list.append(stats.to_dict(py)?)?;
+        }
+        dict.set_item("plane_stats", stats_list)?;
+
         Ok(dict.into())
     }
+
+    /// Build an `ImageMetadata` from the dict shape produced by `to_dict`,
+    /// so Python callers can round-trip metadata back into `encode_tiff`.
+    /// Missing keys fall back to `ImageMetadata::new()` defaults.
+    pub fn from_dict(dict: &Bound<PyDict>) -> PyResult<Self> {
+        let mut metadata = ImageMetadata::new();
+
+        if let Some(v) = dict.get_item("width")? {
+            metadata.width = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("height")? {
+            metadata.height = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("depth")? {
+            metadata.depth = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("channels")? {
+            metadata.channels = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("bit_depth")? {
+            metadata.bit_depth = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("sample_format")? {
+            let raw: String = v.extract()?;
+            metadata.sample_format = SampleFormat::from_str(&raw).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown sample_format: {}", raw))
+            })?;
+        }
+        if let Some(v) = dict.get_item("pixel_size_x")? {
+            metadata.pixel_size_x = Some(v.extract()?);
+        }
+        if let Some(v) = dict.get_item("pixel_size_y")? {
+            metadata.pixel_size_y = Some(v.extract()?);
+        }
+        if let Some(v) = dict.get_item("pixel_size_z")? {
+            metadata.pixel_size_z = Some(v.extract()?);
+        }
+        if let Some(v) = dict.get_item("acquisition_date")? {
+            metadata.acquisition_date = Some(v.extract()?);
+        }
+        if let Some(v) = dict.get_item("microscope_info")? {
+            metadata.microscope_info = Some(v.extract()?);
+        }
+        if let Some(v) = dict.get_item("objective_info")? {
+            metadata.objective_info = Some(v.extract()?);
+        }
+        if let Some(v) = dict.get_item("channel_names")? {
+            metadata.channel_names = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("exposure_times")? {
+            metadata.exposure_times = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("custom_metadata")? {
+            metadata.custom_metadata = v.extract()?;
+        }
+        if let Some(v) = dict.get_item("plane_stats")? {
+            let list: Bound<PyList> = v.extract()?;
+            metadata.plane_stats = list
+                .iter()
+                .map(|item| {
+                    let item_dict = item
+                        .downcast::<PyDict>()
+                        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid plane_stats entry: {}", e)))?;
+                    PlaneStats::from_dict(item_dict)
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+        }
+
+        Ok(metadata)
+    }
 }
\ No newline at end of file
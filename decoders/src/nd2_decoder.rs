@@ -0,0 +1,53 @@
+// Placeholder for ND2 decoder implementation
+// Nikon ND2 format decoder will be implemented here
+
+use crate::metadata::ImageMetadata;
+use crate::pixel_data::PixelData;
+use crate::registry::FormatDecoder;
+use crate::utils::DecodeOptions;
+use pyo3::prelude::*;
+
+pub fn decode_nd2(_file_path: &str) -> PyResult<(Vec<Vec<Vec<u16>>>, ImageMetadata)> {
+    // TODO: Implement ND2 decoder
+    // This will require parsing the ND2 file format specification
+    // and extracting both image data and rich metadata
+
+    Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
+        "ND2 decoder not yet implemented"
+    ))
+}
+
+pub fn get_nd2_metadata(_file_path: &str) -> PyResult<ImageMetadata> {
+    // TODO: Implement ND2 metadata extraction
+
+    Err(PyErr::new::<pyo3::exceptions::PyNotImplementedError, _>(
+        "ND2 metadata extraction not yet implemented"
+    ))
+}
+
+/// `FormatDecoder` adapter for ND2 files. Neither side is implemented yet,
+/// so `ZStackDecoder.capabilities()` can report that honestly.
+pub struct Nd2Format;
+
+impl FormatDecoder for Nd2Format {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["nd2"]
+    }
+
+    fn supports_decode(&self) -> bool {
+        false
+    }
+
+    fn supports_metadata(&self) -> bool {
+        false
+    }
+
+    fn decode(&self, file_path: &str, _options: &DecodeOptions) -> PyResult<(PixelData, ImageMetadata)> {
+        let (data, metadata) = decode_nd2(file_path)?;
+        Ok((PixelData::U16(data), metadata))
+    }
+
+    fn metadata(&self, file_path: &str) -> PyResult<ImageMetadata> {
+        get_nd2_metadata(file_path)
+    }
+}
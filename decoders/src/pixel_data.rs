@@ -0,0 +1,93 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Decoded Z-stack pixel data (depth x channels x pixels), kept in whichever
+/// sample type the source file actually stored so floating-point and
+/// signed-integer microscopy data isn't lossily truncated to `u16`.
+#[derive(Debug, Clone)]
+pub enum PixelData {
+    U16(Vec<Vec<Vec<u16>>>),
+    I32(Vec<Vec<Vec<i32>>>),
+    F32(Vec<Vec<Vec<f32>>>),
+}
+
+impl IntoPy<PyObject> for PixelData {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            PixelData::U16(data) => data.into_py(py),
+            PixelData::I32(data) => data.into_py(py),
+            PixelData::F32(data) => data.into_py(py),
+        }
+    }
+}
+
+impl PixelData {
+    pub fn depth(&self) -> usize {
+        match self {
+            PixelData::U16(planes) => planes.len(),
+            PixelData::I32(planes) => planes.len(),
+            PixelData::F32(planes) => planes.len(),
+        }
+    }
+}
+
+/// Whether decoded samples are unsigned integers, signed integers, or
+/// floating point, mirroring the TIFF `SampleFormat` tag's categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleFormat {
+    Unsigned,
+    Signed,
+    Float,
+}
+
+impl SampleFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SampleFormat::Unsigned => "unsigned",
+            SampleFormat::Signed => "signed",
+            SampleFormat::Float => "float",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "unsigned" => Some(SampleFormat::Unsigned),
+            "signed" => Some(SampleFormat::Signed),
+            "float" => Some(SampleFormat::Float),
+            _ => None,
+        }
+    }
+}
+
+/// Cheap per-plane statistics computed alongside decoding, so callers can
+/// pick the best-focused slice of a Z-stack without a second pass over the
+/// data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaneStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Variance of the Laplacian of the plane; higher values indicate a
+    /// sharper, more in-focus slice.
+    pub focus_metric: f64,
+}
+
+impl PlaneStats {
+    pub fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("min", self.min)?;
+        dict.set_item("max", self.max)?;
+        dict.set_item("mean", self.mean)?;
+        dict.set_item("focus_metric", self.focus_metric)?;
+        Ok(dict.into())
+    }
+
+    pub fn from_dict(dict: &Bound<pyo3::types::PyDict>) -> PyResult<Self> {
+        Ok(PlaneStats {
+            min: dict.get_item("min")?.map(|v| v.extract()).transpose()?.unwrap_or(0.0),
+            max: dict.get_item("max")?.map(|v| v.extract()).transpose()?.unwrap_or(0.0),
+            mean: dict.get_item("mean")?.map(|v| v.extract()).transpose()?.unwrap_or(0.0),
+            focus_metric: dict.get_item("focus_metric")?.map(|v| v.extract()).transpose()?.unwrap_or(0.0),
+        })
+    }
+}
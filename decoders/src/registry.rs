@@ -0,0 +1,43 @@
+use crate::metadata::ImageMetadata;
+use crate::pixel_data::PixelData;
+use crate::utils::DecodeOptions;
+use pyo3::prelude::*;
+
+/// A pluggable backend for one image-stack file format. `ZStackDecoder`
+/// dispatches to whichever registered decoder claims a given file
+/// extension instead of hardcoding a per-extension `match`, so the
+/// capability list it advertises can be checked against what's actually
+/// implemented.
+pub trait FormatDecoder: Send + Sync {
+    /// Lowercase file extensions (no leading dot) this decoder claims.
+    fn extensions(&self) -> &'static [&'static str];
+
+    fn can_decode(&self, ext: &str) -> bool {
+        self.extensions().contains(&ext)
+    }
+
+    /// Whether `decode` can currently return real pixel data for this format.
+    fn supports_decode(&self) -> bool;
+
+    /// Whether `metadata` can currently extract metadata for this format.
+    fn supports_metadata(&self) -> bool;
+
+    fn decode(&self, file_path: &str, options: &DecodeOptions) -> PyResult<(PixelData, ImageMetadata)>;
+
+    fn metadata(&self, file_path: &str) -> PyResult<ImageMetadata>;
+}
+
+/// All known format backends, in the order extension lookups check them.
+pub fn registry() -> Vec<Box<dyn FormatDecoder>> {
+    vec![
+        Box::new(crate::tiff_decoder::TiffFormat),
+        Box::new(crate::lsm_decoder::LsmFormat),
+        Box::new(crate::czi_decoder::CziFormat),
+        Box::new(crate::nd2_decoder::Nd2Format),
+    ]
+}
+
+/// Find the registered decoder that claims `ext`, if any.
+pub fn find<'a>(decoders: &'a [Box<dyn FormatDecoder>], ext: &str) -> Option<&'a dyn FormatDecoder> {
+    decoders.iter().find(|d| d.can_decode(ext)).map(|d| d.as_ref())
+}
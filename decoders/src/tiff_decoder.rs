@@ -1,38 +1,155 @@
 use crate::metadata::ImageMetadata;
+use crate::pixel_data::{PixelData, PlaneStats, SampleFormat};
+use crate::registry::FormatDecoder;
+use crate::utils::{estimate_memory_usage, DecodeOptions};
 use anyhow::{Context, Result};
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Read, Seek};
+use tiff::decoder::ifd::Value;
 use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
 use tiff::ColorType;
 
-pub fn _decode_tiff(file_path: &str) -> PyResult<(Vec<Vec<Vec<u16>>>, ImageMetadata)> {
-    let file = File::open(file_path)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open file: {}", e)))?;
-    
-    let mut decoder = Decoder::new(BufReader::new(file))
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to create TIFF decoder: {}", e)))?;
-    
-    let (width, height) = decoder.dimensions()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to get dimensions: {}", e)))?;
-    
-    let color_type = decoder.colortype()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to get color type: {}", e)))?;
-    
-    let mut metadata = ImageMetadata::new();
-    metadata.width = width;
-    metadata.height = height;
-    metadata.depth = 1; // Will be updated if we find multiple pages
-    
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Byte(v) => Some(*v as f64),
+        Value::Short(v) => Some(*v as f64),
+        Value::SignedByte(v) => Some(*v as f64),
+        Value::SShort(v) => Some(*v as f64),
+        Value::Signed(v) => Some(*v as f64),
+        Value::Unsigned(v) => Some(*v as f64),
+        Value::SignedBig(v) => Some(*v as f64),
+        Value::UnsignedBig(v) => Some(*v as f64),
+        Value::Rational(num, denom) if *denom != 0 => Some(*num as f64 / *denom as f64),
+        Value::SRational(num, denom) if *denom != 0 => Some(*num as f64 / *denom as f64),
+        Value::Float(v) => Some(*v as f64),
+        Value::Double(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn populate_metadata_from_tags<R: Read + Seek>(decoder: &mut Decoder<R>, metadata: &mut ImageMetadata) {
+    let resolution_unit = decoder
+        .find_tag(Tag::ResolutionUnit)
+        .ok()
+        .flatten()
+        .and_then(|v| value_as_f64(&v))
+        .unwrap_or(2.0); // TIFF default: 2 = inch
+
+    // centimeter -> divide 10000 to get um/pixel; inch -> divide 25400
+    let units_per_um = match resolution_unit as u32 {
+        3 => 10_000.0,
+        _ => 25_400.0,
+    };
+
+    if let Some(x_res) = decoder.find_tag(Tag::XResolution).ok().flatten().and_then(|v| value_as_f64(&v)) {
+        if x_res > 0.0 {
+            metadata.pixel_size_x = Some(units_per_um / x_res);
+        }
+    }
+    if let Some(y_res) = decoder.find_tag(Tag::YResolution).ok().flatten().and_then(|v| value_as_f64(&v)) {
+        if y_res > 0.0 {
+            metadata.pixel_size_y = Some(units_per_um / y_res);
+        }
+    }
+
+    if let Ok(date_time) = decoder.get_tag_ascii_string(Tag::DateTime) {
+        metadata.acquisition_date = Some(date_time.trim_end_matches('\0').to_string());
+    }
+
+    if let Ok(description) = decoder.get_tag_ascii_string(Tag::ImageDescription) {
+        parse_image_j_description(&description, metadata);
+    }
+}
+
+// ImageJ's ImageDescription tag embeds newline-separated key=value pairs,
+// e.g. "spacing=1.5\nchannels=2\nunit=um".
+fn parse_image_j_description(description: &str, metadata: &mut ImageMetadata) {
+    for line in description.trim_end_matches('\0').lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        match key {
+            "spacing" => {
+                if let Ok(spacing) = value.parse::<f64>() {
+                    metadata.pixel_size_z = Some(spacing);
+                }
+            }
+            "channels" => {
+                if let Ok(channels) = value.parse::<u32>() {
+                    metadata.channels = channels;
+                }
+            }
+            _ => {
+                metadata.custom_metadata.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+}
+
+/// Check a candidate plane's dimensions and the running decode size against
+/// `options`, returning a descriptive `PyValueError` before any allocation
+/// would exceed them.
+fn check_decode_limits(options: &DecodeOptions, width: u32, height: u32, depth: u32, channels: u32, bit_depth: u16) -> PyResult<()> {
+    if width > options.max_dimensions || height > options.max_dimensions {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Image dimensions {}x{} exceed max_dimensions limit of {}",
+            width, height, options.max_dimensions
+        )));
+    }
+
+    if depth > options.max_depth {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Z-stack depth {} exceeds max_depth limit of {}",
+            depth, options.max_depth
+        )));
+    }
+
+    let estimated_bytes = estimate_memory_usage(width, height, depth, channels.max(1), bit_depth);
+    if estimated_bytes > options.max_memory_bytes {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Decoding this stack would use an estimated {} bytes, exceeding max_memory_bytes limit of {}",
+            estimated_bytes, options.max_memory_bytes
+        )));
+    }
+
+    Ok(())
+}
+
+/// The bit depth of the buffer we actually hand back, which can differ from
+/// the raw TIFF bit depth: `U8` samples are widened to `u16`, and 16-bit
+/// *signed* samples are widened to `i32` (see `decode_plane`). Reads the
+/// `SampleFormat` tag to tell signed integers apart from unsigned ones;
+/// TIFF's default when the tag is absent is unsigned (1).
+fn widened_bit_depth<R: Read + Seek>(decoder: &mut Decoder<R>, bit_depth: u16) -> u16 {
+    const SAMPLE_FORMAT_SIGNED: u32 = 2;
+
+    let sample_format = decoder
+        .find_tag(Tag::SampleFormat)
+        .ok()
+        .flatten()
+        .and_then(|v| value_as_f64(&v))
+        .map(|v| v as u32)
+        .unwrap_or(1);
+
+    match bit_depth {
+        8 => 16,
+        16 if sample_format == SAMPLE_FORMAT_SIGNED => 32,
+        other => other,
+    }
+}
+
+fn set_bit_depth_and_channels(metadata: &mut ImageMetadata, color_type: ColorType) -> PyResult<()> {
     match color_type {
-        ColorType::Gray(8) => metadata.bit_depth = 8,
-        ColorType::Gray(16) => metadata.bit_depth = 16,
-        ColorType::RGB(8) => {
-            metadata.bit_depth = 8;
-            metadata.channels = 3;
+        ColorType::Gray(bits) => {
+            metadata.bit_depth = bits as u16;
         }
-        ColorType::RGB(16) => {
-            metadata.bit_depth = 16;
+        ColorType::RGB(bits) => {
+            metadata.bit_depth = bits as u16;
             metadata.channels = 3;
         }
         _ => {
@@ -41,28 +158,212 @@ pub fn _decode_tiff(file_path: &str) -> PyResult<(Vec<Vec<Vec<u16>>>, ImageMetad
             ));
         }
     }
-    
-    // For now, decode first page only
-    // TODO: Implement multi-page TIFF support for Z-stacks
-    let image_data = decoder.read_image()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to read image: {}", e)))?;
-    
-    let data_3d = match image_data {
+    Ok(())
+}
+
+/// Decode one IFD's raw samples into a single-plane `PixelData`, widening
+/// `U8` to `U16` and `I16` to `I32` so the returned buffer types stay small
+/// in number. Any other sample type the `tiff` crate can produce (`U32`,
+/// `U64`, `I8`, `I64`, `F64`) is rejected with an error.
+fn decode_plane(image_data: DecodingResult) -> PyResult<(SampleFormat, PixelData)> {
+    match image_data {
         DecodingResult::U8(data) => {
             // Convert u8 to u16 for consistency
             let converted: Vec<u16> = data.iter().map(|&x| x as u16).collect();
-            vec![vec![converted]]
+            Ok((SampleFormat::Unsigned, PixelData::U16(vec![vec![converted]])))
         }
-        DecodingResult::U16(data) => {
-            vec![vec![data]]
+        DecodingResult::U16(data) => Ok((SampleFormat::Unsigned, PixelData::U16(vec![vec![data]]))),
+        DecodingResult::I16(data) => {
+            let converted: Vec<i32> = data.iter().map(|&x| x as i32).collect();
+            Ok((SampleFormat::Signed, PixelData::I32(vec![vec![converted]])))
         }
+        DecodingResult::I32(data) => Ok((SampleFormat::Signed, PixelData::I32(vec![vec![data]]))),
+        DecodingResult::F32(data) => Ok((SampleFormat::Float, PixelData::F32(vec![vec![data]]))),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Unsupported data type"
+        )),
+    }
+}
+
+/// Append a single decoded plane onto the running stack, erroring if its
+/// sample type doesn't match the planes decoded so far.
+fn push_plane(stack: &mut PixelData, plane: PixelData) -> PyResult<()> {
+    match (stack, plane) {
+        (PixelData::U16(stack), PixelData::U16(mut plane)) => stack.append(&mut plane),
+        (PixelData::I32(stack), PixelData::I32(mut plane)) => stack.append(&mut plane),
+        (PixelData::F32(stack), PixelData::F32(mut plane)) => stack.append(&mut plane),
         _ => {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Unsupported data type"
+                "Z-stack pages must share the same sample format"
             ));
         }
-    };
-    
+    }
+    Ok(())
+}
+
+/// Variance of the 4-neighbor discrete Laplacian over a `width`x`height`
+/// plane; a standard cheap focus/sharpness measure (higher = sharper).
+fn variance_of_laplacian(samples: &[f64], width: u32, height: u32) -> f64 {
+    let (w, h) = (width as usize, height as usize);
+    if w < 3 || h < 3 || samples.len() < w * h {
+        return 0.0;
+    }
+
+    let mut laplacians = Vec::with_capacity((w - 2) * (h - 2));
+    for y in 1..h - 1 {
+        for x in 1..w - 1 {
+            let idx = y * w + x;
+            let laplacian = 4.0 * samples[idx]
+                - samples[idx - 1]
+                - samples[idx + 1]
+                - samples[idx - w]
+                - samples[idx + w];
+            laplacians.push(laplacian);
+        }
+    }
+
+    let mean = laplacians.iter().sum::<f64>() / laplacians.len() as f64;
+    laplacians.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / laplacians.len() as f64
+}
+
+/// Compute min/max/mean/focus statistics from a plane's samples.
+fn compute_plane_stats(samples: &[f64], width: u32, height: u32) -> PlaneStats {
+    let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mean = samples.iter().sum::<f64>() / samples.len().max(1) as f64;
+    let focus_metric = variance_of_laplacian(samples, width, height);
+
+    PlaneStats { min, max, mean, focus_metric }
+}
+
+fn plane_samples_as_f64(data: &PixelData, index: usize) -> Vec<f64> {
+    match data {
+        PixelData::U16(planes) => planes[index][0].iter().map(|&v| v as f64).collect(),
+        PixelData::I32(planes) => planes[index][0].iter().map(|&v| v as f64).collect(),
+        PixelData::F32(planes) => planes[index][0].iter().map(|&v| v as f64).collect(),
+    }
+}
+
+/// Compute per-plane statistics over an already-decoded stack, optionally
+/// spreading the (pure CPU, read-only) work across a rayon thread pool.
+/// `num_threads` gates the parallelism: `1` keeps this strictly sequential
+/// so single-threaded runs stay reproducible, `0` uses rayon's global pool,
+/// and any other value spins up a dedicated pool of that size. Planes are
+/// decoded and widened to their final type as they're read (see
+/// `_decode_tiff`), so this only ever touches buffers that already exist —
+/// it doesn't hold raw and converted copies of the stack at once.
+fn compute_all_plane_stats(data: &PixelData, width: u32, height: u32, num_threads: usize) -> PyResult<Vec<PlaneStats>> {
+    let depth = data.depth();
+    let process = |index: usize| compute_plane_stats(&plane_samples_as_f64(data, index), width, height);
+
+    if num_threads == 1 {
+        return Ok((0..depth).map(process).collect());
+    }
+
+    if num_threads > 1 {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to build decode thread pool: {}", e)))?;
+        return Ok(pool.install(|| (0..depth).into_par_iter().map(process).collect()));
+    }
+
+    Ok((0..depth).into_par_iter().map(process).collect())
+}
+
+pub fn _decode_tiff(file_path: &str, options: &DecodeOptions) -> PyResult<(PixelData, ImageMetadata)> {
+    let file = File::open(file_path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open file: {}", e)))?;
+
+    let mut decoder = Decoder::new(BufReader::new(file))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to create TIFF decoder: {}", e)))?;
+
+    let (width, height) = decoder.dimensions()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to get dimensions: {}", e)))?;
+
+    let color_type = decoder.colortype()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to get color type: {}", e)))?;
+
+    let mut metadata = ImageMetadata::new();
+    metadata.width = width;
+    metadata.height = height;
+
+    set_bit_depth_and_channels(&mut metadata, color_type)?;
+    populate_metadata_from_tags(&mut decoder, &mut metadata);
+    let output_bit_depth = widened_bit_depth(&mut decoder, metadata.bit_depth);
+
+    // Reading each IFD is inherently sequential (the `tiff` crate advances a
+    // single stream cursor), and each raw plane is decoded, widened to its
+    // final type, and appended to the stack right away so the raw buffer
+    // doesn't stick around once converted — peak memory stays close to what
+    // `check_decode_limits` actually checked, rather than holding a whole
+    // stack's worth of raw and converted buffers at once.
+    let mut sample_format: Option<SampleFormat> = None;
+    let mut data_3d: Option<PixelData> = None;
+    let mut depth: u32 = 0;
+
+    loop {
+        if depth > 0 {
+            if !decoder.more_images() {
+                break;
+            }
+            decoder.next_image()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to advance to next IFD: {}", e)))?;
+
+            let (page_width, page_height) = decoder.dimensions()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to get dimensions: {}", e)))?;
+            let page_color_type = decoder.colortype()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to get color type: {}", e)))?;
+
+            let mut page_metadata = ImageMetadata::new();
+            page_metadata.width = page_width;
+            page_metadata.height = page_height;
+            set_bit_depth_and_channels(&mut page_metadata, page_color_type)?;
+
+            if page_width != metadata.width || page_height != metadata.height || page_metadata.bit_depth != metadata.bit_depth {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!(
+                        "Z-stack pages must share identical dimensions and bit depth: page {} is {}x{} @ {}-bit, expected {}x{} @ {}-bit",
+                        depth + 1, page_width, page_height, page_metadata.bit_depth,
+                        metadata.width, metadata.height, metadata.bit_depth
+                    )
+                ));
+            }
+        }
+
+        check_decode_limits(options, metadata.width, metadata.height, depth + 1, metadata.channels, output_bit_depth)?;
+        let raw = decoder.read_image()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to read image: {}", e)))?;
+        let (plane_sample_format, plane) = decode_plane(raw)?;
+
+        match sample_format {
+            None => sample_format = Some(plane_sample_format),
+            Some(expected) if expected != plane_sample_format => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!(
+                        "Z-stack pages must share the same sample format: page {} is {:?}, expected {:?}",
+                        depth + 1, plane_sample_format, expected
+                    )
+                ));
+            }
+            _ => {}
+        }
+
+        match &mut data_3d {
+            None => data_3d = Some(plane),
+            Some(stack) => push_plane(stack, plane)?,
+        }
+
+        depth += 1;
+    }
+
+    let data_3d = data_3d.ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>("TIFF file contains no image planes")
+    })?;
+    metadata.depth = depth;
+    metadata.sample_format = sample_format.unwrap_or(SampleFormat::Unsigned);
+    metadata.plane_stats = compute_all_plane_stats(&data_3d, metadata.width, metadata.height, options.num_threads)?;
+
     Ok((data_3d, metadata))
 }
 
@@ -85,20 +386,84 @@ pub fn get_tiff_metadata(file_path: &str) -> PyResult<ImageMetadata> {
     metadata.depth = 1;
     
     match color_type {
-        ColorType::Gray(8) => metadata.bit_depth = 8,
-        ColorType::Gray(16) => metadata.bit_depth = 16,
-        ColorType::RGB(8) => {
-            metadata.bit_depth = 8;
-            metadata.channels = 3;
-        }
-        ColorType::RGB(16) => {
-            metadata.bit_depth = 16;
+        ColorType::Gray(bits) => metadata.bit_depth = bits as u16,
+        ColorType::RGB(bits) => {
+            metadata.bit_depth = bits as u16;
             metadata.channels = 3;
         }
         _ => {
             metadata.bit_depth = 0; // Unknown
         }
     }
-    
+
+    populate_metadata_from_tags(&mut decoder, &mut metadata);
+
     Ok(metadata)
+}
+
+/// `FormatDecoder` adapter for plain TIFF files.
+pub struct TiffFormat;
+
+impl FormatDecoder for TiffFormat {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["tiff", "tif"]
+    }
+
+    fn supports_decode(&self) -> bool {
+        true
+    }
+
+    fn supports_metadata(&self) -> bool {
+        true
+    }
+
+    fn decode(&self, file_path: &str, options: &DecodeOptions) -> PyResult<(PixelData, ImageMetadata)> {
+        _decode_tiff(file_path, options)
+    }
+
+    fn metadata(&self, file_path: &str) -> PyResult<ImageMetadata> {
+        get_tiff_metadata(file_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiff_encoder::{self, TiffCompression};
+    use crate::ZStackDecoder;
+    use std::path::PathBuf;
+
+    fn temp_tiff_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("zstack_analyzer_test_{}_{}.tiff", std::process::id(), name));
+        path
+    }
+
+    fn write_test_stack(path: &PathBuf, planes: Vec<Vec<Vec<u16>>>) {
+        let mut metadata = ImageMetadata::new();
+        metadata.width = 4;
+        metadata.height = 4;
+        tiff_encoder::_encode_tiff(&PixelData::U16(planes), &metadata, path.to_str().unwrap(), TiffCompression::Uncompressed).unwrap();
+    }
+
+    // decode_file previously only forwarded to get_tiff_metadata, so a
+    // multi-page TIFF never actually handed pixel data back to Python even
+    // after _decode_tiff grew multi-IFD support. Assert the public entry
+    // point, not just the internal helper.
+    #[test]
+    fn decode_file_returns_pixel_data_for_a_multi_page_stack() {
+        let path = temp_tiff_path("decode_file_multi_page");
+        write_test_stack(&path, vec![vec![vec![1u16; 16]], vec![vec![2u16; 16]]]);
+
+        let depth: u32 = Python::with_gil(|py| {
+            let result = ZStackDecoder::new().decode_file(py, path.to_str().unwrap(), None).unwrap();
+            let dict = result.bind(py).downcast::<pyo3::types::PyDict>().unwrap().clone();
+            assert!(dict.get_item("data").unwrap().is_some(), "decode_file must return pixel data, not just metadata");
+            let metadata = dict.get_item("metadata").unwrap().unwrap();
+            metadata.get_item("depth").unwrap().extract().unwrap()
+        });
+
+        assert_eq!(depth, 2);
+        let _ = std::fs::remove_file(&path);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,253 @@
+use crate::metadata::ImageMetadata;
+use crate::pixel_data::PixelData;
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::BufWriter;
+use tiff::encoder::colortype::{ColorType, Gray16, Gray32};
+use tiff::encoder::compression::{Compression, Deflate, Lzw, Packbits, Uncompressed};
+use tiff::encoder::TiffEncoder;
+use tiff::tags::Tag;
+
+/// Compression algorithm to use when writing a TIFF Z-stack, mirroring what
+/// the `tiff` crate's encoder supports.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiffCompression {
+    Uncompressed,
+    PackBits,
+    Lzw,
+    Deflate,
+}
+
+// TIFF `SampleFormat` tag values (see `tiff_decoder::widened_bit_depth`).
+const SAMPLE_FORMAT_UNSIGNED: u16 = 1;
+const SAMPLE_FORMAT_SIGNED: u16 = 2;
+const SAMPLE_FORMAT_FLOAT: u16 = 3;
+
+pub fn _encode_tiff(
+    data: &PixelData,
+    metadata: &ImageMetadata,
+    file_path: &str,
+    compression: TiffCompression,
+) -> PyResult<()> {
+    let file = File::create(file_path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create file: {}", e)))?;
+
+    let mut encoder = TiffEncoder::new(BufWriter::new(file))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to create TIFF encoder: {}", e)))?;
+
+    let description = image_j_description(data.depth(), metadata);
+
+    match data {
+        // u16 samples need no reinterpretation; written as-is through a
+        // 16-bit unsigned container.
+        PixelData::U16(planes) => {
+            for (index, plane) in planes.iter().enumerate() {
+                let pixels = first_channel(plane, index)?;
+                write_all_compressions::<Gray16>(&mut encoder, metadata, pixels, &description, SAMPLE_FORMAT_UNSIGNED, compression)?;
+            }
+        }
+        // i32/f32 samples share a 32-bit-wide container; the bits are
+        // reinterpreted losslessly and `SampleFormat` records how to read
+        // them back as signed integers or IEEE floats.
+        PixelData::I32(planes) => {
+            for (index, plane) in planes.iter().enumerate() {
+                let pixels: Vec<u32> = first_channel(plane, index)?.iter().map(|&v| v as u32).collect();
+                write_all_compressions::<Gray32>(&mut encoder, metadata, &pixels, &description, SAMPLE_FORMAT_SIGNED, compression)?;
+            }
+        }
+        PixelData::F32(planes) => {
+            for (index, plane) in planes.iter().enumerate() {
+                let pixels: Vec<u32> = first_channel(plane, index)?.iter().map(|&v| v.to_bits()).collect();
+                write_all_compressions::<Gray32>(&mut encoder, metadata, &pixels, &description, SAMPLE_FORMAT_FLOAT, compression)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn first_channel<T>(plane: &[Vec<T>], plane_index: usize) -> PyResult<&[T]> {
+    plane.first().map(Vec::as_slice).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Z-plane {} has no channel data", plane_index))
+    })
+}
+
+fn write_all_compressions<Clr: ColorType>(
+    encoder: &mut TiffEncoder<BufWriter<File>>,
+    metadata: &ImageMetadata,
+    pixels: &[Clr::Inner],
+    description: &str,
+    sample_format: u16,
+    compression: TiffCompression,
+) -> PyResult<()> {
+    match compression {
+        TiffCompression::Uncompressed => write_plane::<Clr, _>(encoder, metadata, pixels, description, sample_format, Uncompressed),
+        TiffCompression::PackBits => write_plane::<Clr, _>(encoder, metadata, pixels, description, sample_format, Packbits::default()),
+        TiffCompression::Lzw => write_plane::<Clr, _>(encoder, metadata, pixels, description, sample_format, Lzw::default()),
+        TiffCompression::Deflate => write_plane::<Clr, _>(encoder, metadata, pixels, description, sample_format, Deflate::default()),
+    }
+}
+
+/// Write a single Z-plane as one IFD, carrying resolution, sample format,
+/// and ImageJ description tags so the output reopens as a calibrated stack
+/// with the right sample type.
+fn write_plane<Clr: ColorType, C: Compression>(
+    encoder: &mut TiffEncoder<BufWriter<File>>,
+    metadata: &ImageMetadata,
+    pixels: &[Clr::Inner],
+    description: &str,
+    sample_format: u16,
+    compression: C,
+) -> PyResult<()> {
+    let mut image = encoder
+        .new_image_with_compression::<Clr, C>(metadata.width, metadata.height, compression)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to start TIFF page: {}", e)))?;
+
+    // XResolution/YResolution are stored as pixels-per-centimeter, the
+    // reciprocal of the um/pixel calibration we carry in metadata.
+    if let Some(px) = metadata.pixel_size_x {
+        if px > 0.0 {
+            let pixels_per_cm = (10_000.0 / px).round() as u32;
+            image
+                .encoder()
+                .write_tag(Tag::XResolution, (pixels_per_cm, 1u32))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to write XResolution tag: {}", e)))?;
+        }
+    }
+    if let Some(py_size) = metadata.pixel_size_y {
+        if py_size > 0.0 {
+            let pixels_per_cm = (10_000.0 / py_size).round() as u32;
+            image
+                .encoder()
+                .write_tag(Tag::YResolution, (pixels_per_cm, 1u32))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to write YResolution tag: {}", e)))?;
+        }
+    }
+    if metadata.pixel_size_x.is_some() || metadata.pixel_size_y.is_some() {
+        const RESOLUTION_UNIT_CENTIMETER: u16 = 3;
+        image
+            .encoder()
+            .write_tag(Tag::ResolutionUnit, RESOLUTION_UNIT_CENTIMETER)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to write ResolutionUnit tag: {}", e)))?;
+    }
+
+    image
+        .encoder()
+        .write_tag(Tag::SampleFormat, sample_format)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to write SampleFormat tag: {}", e)))?;
+
+    image
+        .encoder()
+        .write_tag(Tag::ImageDescription, description)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to write ImageDescription tag: {}", e)))?;
+
+    image
+        .write_data(pixels)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to write plane data: {}", e)))?;
+
+    Ok(())
+}
+
+fn image_j_description(num_planes: usize, metadata: &ImageMetadata) -> String {
+    let spacing = metadata.pixel_size_z.unwrap_or(1.0);
+    format!("ImageJ=1.11a\nimages={}\nspacing={}\nunit=um", num_planes, spacing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiff_decoder;
+    use crate::utils::DecodeOptions;
+    use std::path::PathBuf;
+
+    fn temp_tiff_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("zstack_analyzer_test_{}_{}.tiff", std::process::id(), name));
+        path
+    }
+
+    #[test]
+    fn u16_stack_round_trips_through_encode_and_decode() {
+        let path = temp_tiff_path("roundtrip_u16");
+        let mut metadata = ImageMetadata::new();
+        metadata.width = 3;
+        metadata.height = 3;
+        metadata.pixel_size_x = Some(0.5);
+        metadata.pixel_size_y = Some(0.5);
+        let planes = vec![vec![(0..9).collect::<Vec<u16>>()], vec![(9..18).collect::<Vec<u16>>()]];
+
+        _encode_tiff(&PixelData::U16(planes.clone()), &metadata, path.to_str().unwrap(), TiffCompression::Deflate).unwrap();
+
+        let (data, decoded_metadata) = tiff_decoder::_decode_tiff(path.to_str().unwrap(), &DecodeOptions::default()).unwrap();
+        match data {
+            PixelData::U16(decoded_planes) => assert_eq!(decoded_planes, planes),
+            other => panic!("expected PixelData::U16, got {:?}", other),
+        }
+        assert_eq!(decoded_metadata.depth, 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn f32_stack_round_trips_through_encode_and_decode() {
+        let path = temp_tiff_path("roundtrip_f32");
+        let mut metadata = ImageMetadata::new();
+        metadata.width = 2;
+        metadata.height = 2;
+        let planes = vec![vec![vec![1.5f32, -2.25, 0.0, 100.75]]];
+
+        _encode_tiff(&PixelData::F32(planes.clone()), &metadata, path.to_str().unwrap(), TiffCompression::Uncompressed).unwrap();
+
+        let (data, _) = tiff_decoder::_decode_tiff(path.to_str().unwrap(), &DecodeOptions::default()).unwrap();
+        match data {
+            PixelData::F32(decoded_planes) => assert_eq!(decoded_planes, planes),
+            other => panic!("expected PixelData::F32, got {:?}", other),
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn i32_stack_round_trips_through_encode_and_decode() {
+        let path = temp_tiff_path("roundtrip_i32");
+        let mut metadata = ImageMetadata::new();
+        metadata.width = 2;
+        metadata.height = 2;
+        // Include negative values so the i32 <-> u32 bit-cast used to share
+        // the Gray32 container is actually exercised, not just the
+        // non-negative range that would round-trip even with a lossy cast.
+        let planes = vec![vec![vec![-1i32, i32::MIN, i32::MAX, 0]]];
+
+        _encode_tiff(&PixelData::I32(planes.clone()), &metadata, path.to_str().unwrap(), TiffCompression::Uncompressed).unwrap();
+
+        let (data, _) = tiff_decoder::_decode_tiff(path.to_str().unwrap(), &DecodeOptions::default()).unwrap();
+        match data {
+            PixelData::I32(decoded_planes) => assert_eq!(decoded_planes, planes),
+            other => panic!("expected PixelData::I32, got {:?}", other),
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // A malformed or unexpectedly huge multi-page file should fail before
+    // allocating the offending plane, not after (chunk0-3's whole point).
+    #[test]
+    fn decode_rejects_a_stack_that_exceeds_max_memory_bytes() {
+        let path = temp_tiff_path("negative_memory_limit");
+        let mut metadata = ImageMetadata::new();
+        metadata.width = 16;
+        metadata.height = 16;
+        let planes = vec![vec![vec![0u16; 256]]];
+        _encode_tiff(&PixelData::U16(planes), &metadata, path.to_str().unwrap(), TiffCompression::Uncompressed).unwrap();
+
+        let options = DecodeOptions {
+            max_memory_bytes: 4,
+            ..DecodeOptions::default()
+        };
+        let result = tiff_decoder::_decode_tiff(path.to_str().unwrap(), &options);
+        assert!(result.is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
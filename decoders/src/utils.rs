@@ -1,3 +1,4 @@
+use pyo3::prelude::*;
 use std::path::Path;
 
 pub fn get_file_extension(file_path: &str) -> Option<String> {
@@ -23,4 +24,49 @@ pub fn estimate_memory_usage(width: u32, height: u32, depth: u32, channels: u32,
     };
     
     (width as u64) * (height as u64) * (depth as u64) * (channels as u64) * (bytes_per_pixel as u64)
+}
+
+/// Limits consulted before allocating decoded image data, so a malformed or
+/// unexpectedly huge multi-page file fails fast instead of exhausting memory.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    #[pyo3(get, set)]
+    pub max_memory_bytes: u64,
+    #[pyo3(get, set)]
+    pub max_depth: u32,
+    #[pyo3(get, set)]
+    pub max_dimensions: u32,
+    /// Size of the rayon thread pool used for per-plane statistics work.
+    /// Planes themselves are always read and converted sequentially.
+    /// `0` uses rayon's global default pool (one thread per core); `1`
+    /// computes the statistics sequentially on the calling thread so
+    /// single-threaded runs stay reproducible.
+    #[pyo3(get, set)]
+    pub num_threads: usize,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            max_memory_bytes: 4 * 1024 * 1024 * 1024, // 4 GiB
+            max_depth: u32::MAX,
+            max_dimensions: u32::MAX,
+            num_threads: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl DecodeOptions {
+    #[new]
+    #[pyo3(signature = (max_memory_bytes=4 * 1024 * 1024 * 1024, max_depth=u32::MAX, max_dimensions=u32::MAX, num_threads=0))]
+    pub fn new(max_memory_bytes: u64, max_depth: u32, max_dimensions: u32, num_threads: usize) -> Self {
+        DecodeOptions {
+            max_memory_bytes,
+            max_depth,
+            max_dimensions,
+            num_threads,
+        }
+    }
 }
\ No newline at end of file